@@ -1,30 +1,69 @@
 // File: app.zk_scrypto.rs
 // zk_soundness_vault: simple note-based XRD vault with zk/FHE-style commitments.
+//
+// Known gap: this source tree has no Cargo.toml/test harness, so none of
+// the soundness-critical invariants here (Merkle proof verification,
+// nullifier uniqueness, multi-asset accounting, split/join conservation,
+// issuance backing) have executable test coverage. Add scrypto-test-based
+// unit tests alongside this file once a workspace manifest exists.
 
 use scrypto::prelude::*;
 
-/// A minimal zk-style soundness vault that locks XRD into "notes"
-/// with opaque commitments, inspired by Aztec/Zama-style off-chain systems.
+/// A minimal zk-style soundness vault that locks fungible assets into "notes" with
+/// opaque commitments. The note-commitment tree, nullifier set, and
+/// viewing-key scanning events below follow the shielded-pool designs of
+/// Orchard/Sapling/Aztec/Zama-style off-chain systems.
 #[blueprint]
 mod zk_soundness_vault {
     use scrypto::prelude::*;
 
+    /// Depth of the append-only note-commitment tree. 32 levels gives room
+    /// for 2^32 leaves.
+    const MERKLE_TREE_DEPTH: usize = 32;
+
+    /// Number of historical roots ("anchors") retained for proof
+    /// verification. Withdrawals may prove membership against any anchor in
+    /// this window, not just the very latest root, so proofs generated
+    /// slightly stale still work.
+    const ANCHOR_HISTORY_CAPACITY: u32 = 256;
+
       /// A single locked note in the vault.
     #[derive(ScryptoSbor, Debug, Clone)]
     pub struct Note {
         /// Off-chain zk/FHE commitment (hash, ciphertext, etc).
         pub commitment: String,
-        /// Amount of XRD locked in this note.
+        /// The asset this note's amount is denominated in: the native asset
+        /// or an issued one.
+        pub asset: ResourceAddress,
+        /// Amount of `asset` locked in this note.
         pub amount: Decimal,
         /// Whether this note has been spent.
         pub spent: bool,
+        /// Position of this note's commitment leaf in the note-commitment tree.
+        pub leaf_index: u64,
     }
 
-     #[derive(ScryptoSbor, Debug, Clone, Copy)]
-    pub struct DepositEvent {
+    /// Compact deposit record for off-chain viewing-key scanning: indexers
+    /// trial-decrypt `enc_ciphertext` with their incoming viewing keys
+    /// against each `ephemeral_key`, and correlate successful decryptions
+    /// with `commitment`/`leaf_index` to recover the notes they can spend.
+    #[derive(ScryptoSbor, Debug, Clone)]
+    pub struct CompactDepositEvent {
         pub note_id: u64,
-        pub amount: Decimal,
-        pub opaque_commitment: String,
+        pub commitment: String,
+        pub ephemeral_key: Vec<u8>,
+        pub enc_ciphertext: Vec<u8>,
+        pub leaf_index: u64,
+    }
+
+    /// Compact record for a note created by `split_note`/`join_notes`,
+    /// mirroring `CompactDepositEvent`'s `commitment`/`leaf_index` pair so
+    /// indexers can recover these notes' tree position too.
+    #[derive(ScryptoSbor, Debug, Clone)]
+    pub struct CompactNoteEvent {
+        pub note_id: u64,
+        pub commitment: String,
+        pub leaf_index: u64,
     }
 
     #[derive(ScryptoSbor, Debug, Clone, Copy)]
@@ -34,26 +73,96 @@ mod zk_soundness_vault {
         pub recipient: ComponentAddress,
     }
 
+    /// Emitted when a note is spent via its nullifier rather than its
+    /// public `note_id`. The event carries no `note_id`, but the `leaf`
+    /// argument of `withdraw_with_merkle_proof` is public, so an indexer can
+    /// still match the spend to the deposit it came from.
+    #[derive(ScryptoSbor, Debug, Clone, Copy)]
+    pub struct NullifierSpentEvent {
+        pub nullifier: Hash,
+        pub amount: Decimal,
+        pub recipient: ComponentAddress,
+    }
+
+    /// Emitted when a note is minted via `issue_note` rather than backed by
+    /// a matching deposit.
+    #[derive(ScryptoSbor, Debug, Clone)]
+    pub struct IssuanceEvent {
+        pub note_id: u64,
+        pub asset: ResourceAddress,
+        pub amount: Decimal,
+        pub commitment: String,
+        pub leaf_index: u64,
+    }
+
     pub struct ZkSoundnessVault {
-        /// Vault holding all locked XRD.
-        vault: Vault,
+        /// One vault per locked asset, created lazily on first deposit.
+        vaults: KeyValueStore<ResourceAddress, Vault>,
         /// Mapping from note_id -> note data.
         notes: KeyValueStore<u64, Note>,
         /// Next note id to assign (also equal to number of notes ever created).
         next_note_id: u64,
-        /// Total XRD currently locked across all unspent notes.
-        total_locked: Decimal,
+        /// Total amount currently locked across all unspent notes, per asset.
+        total_locked: KeyValueStore<ResourceAddress, Decimal>,
+        /// Rightmost filled subtree hash at each level of the note-commitment
+        /// tree, maintained incrementally as leaves are appended.
+        frontier: Vec<Hash>,
+        /// Number of leaves appended to the note-commitment tree so far;
+        /// also the position assigned to the next leaf.
+        next_leaf_index: u64,
+        /// Ring buffer of the last `ANCHOR_HISTORY_CAPACITY` roots ("anchors")
+        /// of the note-commitment tree, indexed by a monotonically increasing
+        /// slot modulo the capacity.
+        anchors: KeyValueStore<u32, Hash>,
+        /// Total number of anchors ever published; `anchors` holds the most
+        /// recent `min(anchor_count, ANCHOR_HISTORY_CAPACITY)` of them.
+        anchor_count: u32,
+        /// Set of nullifiers that have already been spent, keyed separately
+        /// from `notes` so double-spend checks don't require knowing which
+        /// note a nullifier belongs to.
+        nullifiers: KeyValueStore<Hash, ()>,
+        /// Maps a note's commitment leaf hash back to its `note_id`, so
+        /// `withdraw_with_merkle_proof` can look a note up by `leaf` instead
+        /// of by its sequential `note_id`.
+        commitment_index: KeyValueStore<Hash, u64>,
+        /// Badge resource authorized to mint issued-asset notes via
+        /// `issue_note`, if issuance is enabled for this vault.
+        issuance_authority: Option<ResourceAddress>,
+        /// Total amount ever minted via `issue_note`, per asset. This is a
+        /// subset of `total_locked` (which folds minted supply in alongside
+        /// deposited supply so withdrawals are always backed by real vault
+        /// funds); tracking it separately lets off-chain verifiers audit
+        /// that issued supply never exceeds the total locked for an asset.
+        issued_supply: KeyValueStore<ResourceAddress, Decimal>,
     }
 
     impl ZkSoundnessVault {
               /// Instantiate a new zk soundness vault component with an empty XRD vault
         /// and no owner (OwnerRole::None).
         pub fn instantiate() -> Global<ZkSoundnessVault> {
+            Self::instantiate_with_issuance_authority(None)
+        }
+
+        /// Instantiate a new zk soundness vault, optionally designating
+        /// `issuance_authority` as the badge resource allowed to mint
+        /// issued-asset notes via `issue_note`. Pass `None` to disable
+        /// issuance entirely (the behavior of `instantiate`).
+        pub fn instantiate_with_issuance_authority(
+            issuance_authority: Option<ResourceAddress>,
+        ) -> Global<ZkSoundnessVault> {
             let component = Self {
-                              vault: Vault::new(VAULT_RESOURCE),
+                              vaults: KeyValueStore::new(),
                 notes: KeyValueStore::new(),
                 next_note_id: 0,
-                total_locked: Decimal::ZERO,
+                total_locked: KeyValueStore::new(),
+                frontier: Self::empty_subtree_hashes(),
+                next_leaf_index: 0,
+                anchors: KeyValueStore::new(),
+                anchor_count: 0,
+                nullifiers: KeyValueStore::new(),
+                commitment_index: KeyValueStore::new(),
+                issuance_authority,
+                issued_supply: KeyValueStore::new(),
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
@@ -62,39 +171,54 @@ mod zk_soundness_vault {
             component
         }
 
-        pub fn deposit_with_commitment(&mut self, mut payment: Bucket, commitment: String) -> u64 {
+        pub fn deposit_with_commitment(
+            &mut self,
+            mut payment: Bucket,
+            commitment: String,
+            ephemeral_key: Vec<u8>,
+            enc_ciphertext: Vec<u8>,
+        ) -> u64 {
                        assert!(
-                payment.resource_address() == VAULT_RESOURCE,
-                "Only XRD deposits are supported in this minimal example"
+                ResourceManager::from(payment.resource_address())
+                    .resource_type()
+                    .is_fungible(),
+                "Only fungible asset deposits are supported in this minimal example"
             );
 
+            let asset = payment.resource_address();
             let amount = payment.amount();
                       assert!(
                 amount.is_positive(),
                 "Deposit amount must be strictly positive"
             );
 
-            self.vault.put(payment);
+            self.asset_vault_mut(asset).put(payment);
 
             let note_id = self.next_note_id;
             self.next_note_id += 1;
-            self.total_locked += amount;
+            let locked_before = self.total_locked.get(&asset).unwrap_or(Decimal::ZERO);
+            self.total_locked.insert(asset, locked_before + amount);
+
+            let leaf = hash(commitment.as_bytes());
+            let leaf_index = self.append_leaf(leaf);
+            self.commitment_index.insert(leaf, note_id);
 
             let note = Note {
-                commitment,
+                commitment: commitment.clone(),
+                asset,
                 amount,
                 spent: false,
+                leaf_index,
             };
 
             self.notes.insert(note_id, note);
 
-            // This event is intentionally minimal: off-chain systems inspired by
-            // Aztec, Zama, or other soundness-focused zk frameworks are expected
-            // to index these events and correlate them with encrypted state.
-            emit_event(DepositEvent {
+            emit_event(CompactDepositEvent {
                 note_id,
-                amount,
-                opaque_commitment: String::from("opaque:stored-off-chain (Aztec/Zama style)"),
+                commitment,
+                ephemeral_key,
+                enc_ciphertext,
+                leaf_index,
             });
 
             note_id
@@ -111,8 +235,10 @@ mod zk_soundness_vault {
                 note.amount > Decimal::ZERO,
                 "Note amount must be positive to withdraw"
             );
+            let asset = note.asset;
+            let locked = self.total_locked.get(&asset).unwrap_or(Decimal::ZERO);
             assert!(
-                self.total_locked >= note.amount,
+                locked >= note.amount,
                 "Vault invariant broken: insufficient locked amount; soundness violation"
             );
 
@@ -124,8 +250,8 @@ mod zk_soundness_vault {
             note.amount = Decimal::ZERO;
             self.notes.insert(note_id, note);
 
-            self.total_locked -= amount;
-            let withdrawn = self.vault.take(amount);
+            self.total_locked.insert(asset, locked - amount);
+            let withdrawn = self.asset_vault_mut(asset).take(amount);
 
             emit_event(WithdrawalEvent {
                 note_id,
@@ -135,13 +261,317 @@ mod zk_soundness_vault {
 
             withdrawn
         }
-        /// Return the resource address managed by this vault (XRD in this example).
+
+        /// Split `note_id` into several new notes without moving any funds
+        /// in or out of the vault. `amounts` and `commitments` must be the
+        /// same length; `amounts` must sum exactly to the parent note's
+        /// amount, so value is neither created nor destroyed. Returns the
+        /// new notes' ids, in the same order as `amounts`/`commitments`.
+        pub fn split_note(
+            &mut self,
+            note_id: u64,
+            amounts: Vec<Decimal>,
+            commitments: Vec<String>,
+        ) -> Vec<u64> {
+            assert!(
+                amounts.len() == commitments.len(),
+                "Must supply exactly one commitment per output amount"
+            );
+            assert!(!amounts.is_empty(), "Must split into at least one note");
+
+            let mut parent = self
+                .notes
+                .get(&note_id)
+                .expect("Unknown note id in zk_soundness_vault");
+            assert!(!parent.spent, "Note already spent");
+
+            let children_total = amounts
+                .iter()
+                .fold(Decimal::ZERO, |acc, amount| acc + *amount);
+            assert!(
+                children_total == parent.amount,
+                "Split amounts must sum exactly to the parent note's amount; soundness violation"
+            );
+
+            let asset = parent.asset;
+            let locked_before = self.get_total_locked_for(asset);
+
+            parent.spent = true;
+            self.notes.insert(note_id, parent);
+
+            let mut child_ids = Vec::with_capacity(amounts.len());
+            for (amount, commitment) in amounts.into_iter().zip(commitments.into_iter()) {
+                assert!(
+                    amount.is_positive(),
+                    "Split output amounts must be strictly positive"
+                );
+                let commitment_for_event = commitment.clone();
+                let (child_id, leaf_index) = self.create_unspent_note(asset, amount, commitment);
+                emit_event(CompactNoteEvent {
+                    note_id: child_id,
+                    commitment: commitment_for_event,
+                    leaf_index,
+                });
+                child_ids.push(child_id);
+            }
+
+            assert!(
+                self.get_total_locked_for(asset) == locked_before,
+                "Vault invariant broken: split changed total locked amount; soundness violation"
+            );
+
+            child_ids
+        }
+
+        /// Join several notes into a single new note without moving any
+        /// funds in or out of the vault. All input notes must carry the same
+        /// asset; the new note's amount is their sum.
+        pub fn join_notes(&mut self, note_ids: Vec<u64>, commitment: String) -> u64 {
+            assert!(!note_ids.is_empty(), "Must join at least one note");
+            let unique_ids: IndexSet<u64> = note_ids.iter().copied().collect();
+            assert!(
+                unique_ids.len() == note_ids.len(),
+                "Cannot join the same note id more than once; soundness violation"
+            );
+
+            let mut asset: Option<ResourceAddress> = None;
+            let mut total = Decimal::ZERO;
+            let mut parents = Vec::with_capacity(note_ids.len());
+            for note_id in note_ids.iter() {
+                let parent = self
+                    .notes
+                    .get(note_id)
+                    .expect("Unknown note id in zk_soundness_vault");
+                assert!(!parent.spent, "Note already spent");
+                match asset {
+                    None => asset = Some(parent.asset),
+                    Some(expected) => assert!(
+                        parent.asset == expected,
+                        "Cannot join notes denominated in different assets"
+                    ),
+                }
+                total += parent.amount;
+                parents.push(parent);
+            }
+            let asset = asset.expect("Must join at least one note");
+            let locked_before = self.get_total_locked_for(asset);
+
+            for (note_id, mut parent) in note_ids.into_iter().zip(parents.into_iter()) {
+                parent.spent = true;
+                self.notes.insert(note_id, parent);
+            }
+
+            let commitment_for_event = commitment.clone();
+            let (joined_id, leaf_index) = self.create_unspent_note(asset, total, commitment);
+            emit_event(CompactNoteEvent {
+                note_id: joined_id,
+                commitment: commitment_for_event,
+                leaf_index,
+            });
+
+            assert!(
+                self.get_total_locked_for(asset) == locked_before,
+                "Vault invariant broken: join changed total locked amount; soundness violation"
+            );
+
+            joined_id
+        }
+
+        /// Mint a new asset-backed note for `asset`/`amount` without a
+        /// matching deposit bucket, gated by `proof_of_issuance` presenting
+        /// the vault's `issuance_authority` badge. The vault itself mints
+        /// `amount` of `asset` (so the resulting note is genuinely backed by
+        /// real funds in the per-asset vault, not just bookkeeping) and folds
+        /// it into `total_locked` alongside deposited supply. `issued_supply`
+        /// tracks the issued portion separately so off-chain verifiers can
+        /// audit that it never exceeds `total_locked` overall.
+        pub fn issue_note(
+            &mut self,
+            proof_of_issuance: Proof,
+            asset: ResourceAddress,
+            amount: Decimal,
+            commitment: String,
+        ) -> u64 {
+            let authority = self
+                .issuance_authority
+                .expect("Issuance is not enabled for this vault");
+            assert!(
+                proof_of_issuance.resource_address() == authority,
+                "Invalid issuance proof: wrong badge resource"
+            );
+            proof_of_issuance.drop();
+
+            assert!(
+                ResourceManager::from(asset).resource_type().is_fungible(),
+                "Only fungible asset issuance is supported in this minimal example"
+            );
+            assert!(
+                amount.is_positive(),
+                "Issued amount must be strictly positive"
+            );
+
+            let minted = ResourceManager::from(asset).mint(amount);
+            self.asset_vault_mut(asset).put(minted);
+
+            let locked_before = self.total_locked.get(&asset).unwrap_or(Decimal::ZERO);
+            self.total_locked.insert(asset, locked_before + amount);
+
+            let issued_before = self.issued_supply.get(&asset).unwrap_or(Decimal::ZERO);
+            self.issued_supply.insert(asset, issued_before + amount);
+
+            assert!(
+                self.get_issued_supply_for(asset) <= self.get_total_locked_for(asset),
+                "Vault invariant broken: issued supply exceeds total locked; soundness violation"
+            );
+
+            let (note_id, leaf_index) = self.create_unspent_note(asset, amount, commitment.clone());
+
+            emit_event(IssuanceEvent {
+                note_id,
+                asset,
+                amount,
+                commitment,
+                leaf_index,
+            });
+
+            note_id
+        }
+
+        /// Total amount ever minted for `asset` via `issue_note`.
+        pub fn get_issued_supply_for(&self, asset: ResourceAddress) -> Decimal {
+            self.issued_supply.get(&asset).unwrap_or(Decimal::ZERO)
+        }
+
+        /// Withdraw by proving membership of a commitment leaf against a
+        /// known `anchor`. The caller identifies the note by `leaf` (the
+        /// commitment hash, looked up via `commitment_index`) rather than by
+        /// its sequential `note_id`, and supplies the sibling hash at each
+        /// level of the tree along the path from the note's committed
+        /// `leaf_index` up to the root (`auth_path`); the component
+        /// recomputes the root from `leaf`/`auth_path`, checks it matches
+        /// `anchor`, and releases the note's own stored `asset`/`amount` —
+        /// never a caller-supplied amount or asset, so a valid proof can
+        /// only ever redeem the exact note it proves membership for.
+        ///
+        /// Note that `leaf` and `auth_path` are ordinary public call
+        /// arguments, and `leaf` is the same commitment hash already
+        /// published in `CompactDepositEvent`/`CompactNoteEvent`/
+        /// `IssuanceEvent`, so an indexer watching those events can still
+        /// correlate this withdrawal with the deposit it spends. `nullifier`
+        /// (an off-chain-derived value unique per note) only prevents the
+        /// same note being spent twice; it does not by itself hide which
+        /// note was spent. Real unlinkability would require the leaf to stay
+        /// hidden inside a verified zk proof instead of being checked in the
+        /// clear, which this minimal example does not implement.
+        pub fn withdraw_with_merkle_proof(
+            &mut self,
+            leaf: Hash,
+            auth_path: Vec<Hash>,
+            anchor: Hash,
+            nullifier: Hash,
+            recipient: ComponentAddress,
+        ) -> Bucket {
+            let note_id = self
+                .commitment_index
+                .get(&leaf)
+                .expect("Unknown commitment leaf in zk_soundness_vault");
+            let mut note = self
+                .notes
+                .get(&note_id)
+                .expect("Unknown note id in zk_soundness_vault");
+            assert!(!note.spent, "Note already spent");
+            assert!(
+                auth_path.len() == MERKLE_TREE_DEPTH,
+                "Auth path must supply exactly one sibling per tree level"
+            );
+            assert!(
+                self.is_known_anchor(&anchor),
+                "Anchor is not a known root of the note-commitment tree"
+            );
+            assert!(
+                self.nullifiers.get(&nullifier).is_none(),
+                "Nullifier already spent: double spend / soundness violation"
+            );
+
+            let asset = note.asset;
+            let amount = note.amount;
+            let locked = self.total_locked.get(&asset).unwrap_or(Decimal::ZERO);
+            assert!(
+                locked >= amount,
+                "Vault invariant broken: insufficient locked amount; soundness violation"
+            );
+
+            assert!(
+                leaf == hash(note.commitment.as_bytes()),
+                "Leaf does not match the note's stored commitment"
+            );
+            let recomputed = Self::root_from_path(leaf, note.leaf_index, &auth_path);
+            assert!(
+                recomputed == anchor,
+                "Merkle proof does not verify against the supplied anchor"
+            );
+
+            note.spent = true;
+            self.notes.insert(note_id, note);
+            self.nullifiers.insert(nullifier, ());
+
+            self.total_locked.insert(asset, locked - amount);
+            let withdrawn = self.asset_vault_mut(asset).take(amount);
+
+            emit_event(NullifierSpentEvent {
+                nullifier,
+                amount,
+                recipient,
+            });
+
+            withdrawn
+        }
+
+        /// Whether `nullifier` has already been spent, for off-chain indexers
+        /// that want to skip re-processing a known-spent note.
+        pub fn is_nullifier_spent(&self, nullifier: Hash) -> bool {
+            self.nullifiers.get(&nullifier).is_some()
+        }
+
+        /// Return the most recently published anchor (root) of the
+        /// note-commitment tree.
+        pub fn get_current_anchor(&self) -> Hash {
+            Self::root_from_frontier(&self.frontier, self.next_leaf_index)
+        }
+
+        /// Return all anchors currently retained in the ring buffer, oldest
+        /// first.
+        pub fn get_anchors(&self) -> Vec<Hash> {
+            let retained = core::cmp::min(self.anchor_count, ANCHOR_HISTORY_CAPACITY);
+            let oldest_slot = self.anchor_count - retained;
+            (0..retained)
+                .map(|i| {
+                    let slot = (oldest_slot + i) % ANCHOR_HISTORY_CAPACITY;
+                    self.anchors
+                        .get(&slot)
+                        .expect("Anchor slot missing within retained window")
+                        .clone()
+                })
+                .collect()
+        }
+
+        /// Return the resource address of this vault's native example asset (XRD).
         pub fn get_vault_resource_address(&self) -> ResourceAddress {
             VAULT_RESOURCE
         }
 
+        /// Sum of locked amounts across every asset the vault holds notes for.
+        /// O(n) over the number of distinct assets ever deposited.
         pub fn get_total_locked(&self) -> Decimal {
             self.total_locked
+                .iter()
+                .map(|(_, locked)| locked)
+                .fold(Decimal::ZERO, |acc, locked| acc + locked)
+        }
+
+        /// Total amount currently locked for a single `asset`.
+        pub fn get_total_locked_for(&self, asset: ResourceAddress) -> Decimal {
+            self.total_locked.get(&asset).unwrap_or(Decimal::ZERO)
         }
 
             /// Return the number of notes ever created.
@@ -157,8 +587,8 @@ mod zk_soundness_vault {
         pub fn get_note_metadata(&self, note_id: u64) -> Option<Note> {
             self.notes.get(&note_id)
         }
-    }
-            /// Approximate count of unspent notes by scanning the store.
+
+        /// Approximate count of unspent notes by scanning the store.
         /// This is O(n) over the number of notes and intended for light use.
         pub fn get_unspent_note_count(&self) -> u64 {
             let mut count: u64 = 0;
@@ -171,4 +601,136 @@ mod zk_soundness_vault {
             count
         }
 
+        /// Create a new unspent note for `asset`/`amount` backed by
+        /// `commitment`, appending its leaf to the note-commitment tree.
+        /// Used by note arithmetic (`split_note`/`join_notes`) where no funds
+        /// actually move in or out of the vault.
+        fn create_unspent_note(
+            &mut self,
+            asset: ResourceAddress,
+            amount: Decimal,
+            commitment: String,
+        ) -> (u64, u64) {
+            let note_id = self.next_note_id;
+            self.next_note_id += 1;
+
+            let leaf = hash(commitment.as_bytes());
+            let leaf_index = self.append_leaf(leaf);
+            self.commitment_index.insert(leaf, note_id);
+
+            let note = Note {
+                commitment,
+                asset,
+                amount,
+                spent: false,
+                leaf_index,
+            };
+            self.notes.insert(note_id, note);
+
+            (note_id, leaf_index)
+        }
+
+        /// Return a mutable reference to the vault for `asset`, creating an
+        /// empty one on first use (lazy per-asset vault creation).
+        fn asset_vault_mut(&mut self, asset: ResourceAddress) -> KeyValueEntryRefMut<Vault> {
+            if self.vaults.get(&asset).is_none() {
+                self.vaults.insert(asset, Vault::new(asset));
+            }
+            self.vaults
+                .get_mut(&asset)
+                .expect("Vault was just inserted for this asset")
+        }
+
+        /// Append `leaf` to the incremental note-commitment tree, updating
+        /// the frontier and publishing the new root as an anchor. Returns the
+        /// position assigned to `leaf`.
+        fn append_leaf(&mut self, leaf: Hash) -> u64 {
+            let position = self.next_leaf_index;
+            let mut idx = position;
+            let mut cur = leaf;
+            for level in 0..MERKLE_TREE_DEPTH {
+                if idx & 1 == 0 {
+                    self.frontier[level] = cur;
+                    break;
+                }
+                cur = Self::hash_pair(&self.frontier[level], &cur);
+                idx >>= 1;
+            }
+
+            self.next_leaf_index += 1;
+
+            let root = Self::root_from_frontier(&self.frontier, self.next_leaf_index);
+            let slot = self.anchor_count % ANCHOR_HISTORY_CAPACITY;
+            self.anchors.insert(slot, root);
+            self.anchor_count += 1;
+
+            position
+        }
+
+        /// Recompute the tree root by folding the frontier against
+        /// precomputed empty-subtree hashes for the missing right siblings.
+        fn root_from_frontier(frontier: &[Hash], leaf_count: u64) -> Hash {
+            let empty = Self::empty_subtree_hashes();
+            let mut idx = leaf_count;
+            let mut node = empty[0];
+            for level in 0..MERKLE_TREE_DEPTH {
+                if idx & 1 == 1 {
+                    node = Self::hash_pair(&frontier[level], &node);
+                } else {
+                    node = Self::hash_pair(&node, &empty[level]);
+                }
+                idx >>= 1;
+            }
+            node
+        }
+
+        /// Recompute a tree root from a leaf, its position, and the sibling
+        /// hash at each level along the path from `position` to the root.
+        fn root_from_path(leaf: Hash, position: u64, auth_path: &[Hash]) -> Hash {
+            let mut idx = position;
+            let mut node = leaf;
+            for sibling in auth_path.iter() {
+                node = if idx & 1 == 0 {
+                    Self::hash_pair(&node, sibling)
+                } else {
+                    Self::hash_pair(sibling, &node)
+                };
+                idx >>= 1;
+            }
+            node
+        }
+
+        /// Precomputed hash of an empty subtree at each level: level 0 is the
+        /// hash of an empty leaf, and each subsequent level hashes the pair of
+        /// the previous level's empty hash with itself.
+        fn empty_subtree_hashes() -> Vec<Hash> {
+            let mut hashes = Vec::with_capacity(MERKLE_TREE_DEPTH);
+            let mut current = hash(Vec::<u8>::new());
+            hashes.push(current);
+            for _ in 1..MERKLE_TREE_DEPTH {
+                current = Self::hash_pair(&current, &current);
+                hashes.push(current);
+            }
+            hashes
+        }
+
+        fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+            let mut bytes = Vec::with_capacity(64);
+            bytes.extend_from_slice(left.as_bytes());
+            bytes.extend_from_slice(right.as_bytes());
+            hash(bytes)
+        }
+
+        fn is_known_anchor(&self, anchor: &Hash) -> bool {
+            if *anchor == self.get_current_anchor() {
+                return true;
+            }
+            let retained = core::cmp::min(self.anchor_count, ANCHOR_HISTORY_CAPACITY);
+            let oldest_slot = self.anchor_count - retained;
+            (0..retained).any(|i| {
+                let slot = (oldest_slot + i) % ANCHOR_HISTORY_CAPACITY;
+                self.anchors.get(&slot).map(|h| *h == *anchor).unwrap_or(false)
+            })
+        }
+    }
 }
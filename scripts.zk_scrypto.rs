@@ -40,28 +40,35 @@ mod zk_soundness_vault_scripts {
             (vault, wrapper)
         }
 
-        /// Deposit XRD into the vault with a commitment.
+        /// Deposit an asset into the vault with a commitment and an
+        /// encrypted compact note payload.
         ///
-        /// - `xrd`: bucket of XRD (taken from the caller’s account in the manifest)
+        /// - `xrd`: bucket of the asset (taken from the caller’s account in the manifest)
         /// - `commitment`: opaque string created off-chain (hash, encrypted note, etc.)
+        /// - `ephemeral_key`: per-deposit ephemeral public key used to derive the encryption key
+        /// - `enc_ciphertext`: note plaintext (amount, asset, memo, ...) encrypted to the recipient's viewing key
         ///
         /// Returns: `note_id` created by the underlying vault.
         pub fn deposit_with_commitment_script(
             &mut self,
             xrd: Bucket,
             commitment: String,
+            ephemeral_key: Vec<u8>,
+            enc_ciphertext: Vec<u8>,
         ) -> u64 {
             // Assumes underlying blueprint method:
-            //   pub fn deposit_with_commitment(&mut self, xrd: Bucket, commitment: String) -> u64
-            self.vault.deposit_with_commitment(xrd, commitment)
+            //   pub fn deposit_with_commitment(&mut self, xrd: Bucket, commitment: String, ephemeral_key: Vec<u8>, enc_ciphertext: Vec<u8>) -> u64
+            self.vault
+                .deposit_with_commitment(xrd, commitment, ephemeral_key, enc_ciphertext)
         }
-        /// Convenience: deposit XRD with an empty commitment string.
+        /// Convenience: deposit an asset with an empty commitment and no encrypted payload.
         pub fn deposit_with_empty_commitment_script(
             &mut self,
             xrd: Bucket,
         ) -> u64 {
             let commitment = String::new();
-            self.vault.deposit_with_commitment(xrd, commitment)
+            self.vault
+                .deposit_with_commitment(xrd, commitment, Vec::new(), Vec::new())
         }
 
               /// Withdraw using a note id, sending the XRD to the given recipient.
@@ -102,6 +109,11 @@ mod zk_soundness_vault_scripts {
             self.vault.get_total_locked()
         }
 
+        /// Read-only helper: total amount locked for a single asset.
+        pub fn get_total_locked_for_via_script(&self, asset: ResourceAddress) -> Decimal {
+            self.vault.get_total_locked_for(asset)
+        }
+
         /// Read-only helper: how many notes have been created so far.
         pub fn get_note_count_via_script(&self) -> u64 {
             self.vault.get_note_count()